@@ -0,0 +1,4 @@
+//! Rust bindings and tools for working with the `ObjectProperty`
+//! serialization format used by KingsIsle's game engine.
+
+pub mod object_property;
@@ -0,0 +1,20 @@
+//! The `ObjectProperty` binary (de)serialization format used
+//! throughout KingsIsle's game engine to persist and transmit
+//! game object state.
+
+mod reader;
+mod serialization;
+mod type_list;
+mod value;
+mod value_serde;
+mod writer;
+
+pub use self::reader::{BitRead, IoReader, SliceReader};
+pub use self::serialization::{
+    Deserializer, DeserializerOptions, Serializer, SerializerFlags, SerializerOptions,
+};
+pub use self::type_list::{
+    CoreObject, PropertyClass, PropertyDef, PropertyFlags, PropertyType, TypeDef, TypeList,
+    TypeTag,
+};
+pub use self::value::{EnumValue, Value};
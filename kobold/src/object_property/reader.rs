@@ -0,0 +1,184 @@
+use std::io::{self, Read};
+
+use byteorder::{ReadBytesExt, LE};
+
+/// A source of bits and bytes for the [`Deserializer`](super::Deserializer)
+/// to read from, abstracting over a fully-buffered slice and a
+/// streaming [`io::Read`].
+///
+/// Bits are consumed least-significant-first within a byte. Call
+/// [`BitRead::realign_to_byte`] before any byte-aligned read to
+/// discard a partially consumed byte.
+pub trait BitRead {
+    /// Discards a partially read byte, if any, so the next read
+    /// starts at a byte boundary.
+    fn realign_to_byte(&mut self);
+
+    /// Reads a single bit from the stream.
+    fn read_bit(&mut self) -> io::Result<bool>;
+
+    /// Reads `n` bits into the low bits of a `u64`,
+    /// least-significant bit first.
+    fn read_value_bits(&mut self, n: usize) -> io::Result<u64> {
+        let mut value = 0u64;
+        for i in 0..n {
+            if self.read_bit()? {
+                value |= 1 << i;
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Reads `len` raw bytes, realigning to a byte boundary
+    /// first.
+    fn read_bytes(&mut self, len: usize) -> io::Result<Vec<u8>>;
+}
+
+macro_rules! impl_load {
+    ($($load:ident() -> $ty:ty = $read:ident($($arg:ty)?)),* $(,)*) => {
+        $(
+            #[doc = concat!("Reads a byte-aligned little-endian `", stringify!($ty), "`.")]
+            fn $load(&mut self) -> io::Result<$ty> {
+                let mut bytes = &self.read_bytes(std::mem::size_of::<$ty>())?[..];
+                bytes.$read$(::<$arg>)?()
+            }
+        )*
+    };
+}
+
+/// Byte-aligned scalar reads built on top of [`BitRead::read_bytes`].
+///
+/// Split out from [`BitRead`] only so its default methods don't
+/// have to be repeated by every implementor.
+pub trait BitReadExt: BitRead {
+    impl_load! {
+        load_u8() -> u8 = read_u8(),
+        load_u16() -> u16 = read_u16(LE),
+        load_u32() -> u32 = read_u32(LE),
+        load_u64() -> u64 = read_u64(LE),
+
+        load_i8() -> i8 = read_i8(),
+        load_i16() -> i16 = read_i16(LE),
+        load_i32() -> i32 = read_i32(LE),
+
+        load_f32() -> f32 = read_f32(LE),
+        load_f64() -> f64 = read_f64(LE),
+    }
+}
+
+impl<R: BitRead + ?Sized> BitReadExt for R {}
+
+/// A [`BitRead`] over a borrowed in-memory byte slice.
+///
+/// The input is not buffered up front, but each
+/// [`BitRead::read_bytes`] call (and so each byte-aligned scalar
+/// load) copies the requested range into an owned `Vec`.
+#[derive(Default)]
+pub struct SliceReader<'de> {
+    data: &'de [u8],
+    pos: usize,
+    bit: u8,
+}
+
+impl<'de> SliceReader<'de> {
+    /// Creates a new slice reader over `data`.
+    pub fn new(data: &'de [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bit: 0,
+        }
+    }
+
+    fn eof() -> io::Error {
+        io::Error::new(io::ErrorKind::UnexpectedEof, "ran out of data to read")
+    }
+}
+
+impl<'de> BitRead for SliceReader<'de> {
+    fn realign_to_byte(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+
+    fn read_bit(&mut self) -> io::Result<bool> {
+        let byte = *self.data.get(self.pos).ok_or_else(Self::eof)?;
+        let value = (byte >> self.bit) & 1 != 0;
+
+        self.bit += 1;
+        if self.bit == u8::BITS as u8 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+
+        Ok(value)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        self.realign_to_byte();
+
+        let end = self.pos.checked_add(len).ok_or_else(Self::eof)?;
+        let bytes = self.data.get(self.pos..end).ok_or_else(Self::eof)?.to_vec();
+        self.pos = end;
+
+        Ok(bytes)
+    }
+}
+
+/// A buffered [`BitRead`] that pulls bytes on demand from any
+/// [`io::Read`], for deserializing directly off a stream (a
+/// socket, a file) without buffering the whole payload up front.
+///
+/// As with [`SliceReader`], a realign discards the rest of a
+/// partially consumed byte - here it only resets the bit cursor,
+/// since the underlying stream has already produced that byte.
+pub struct IoReader<R> {
+    inner: io::BufReader<R>,
+    cur: u8,
+    bit: u8,
+}
+
+impl<R: Read> IoReader<R> {
+    /// Creates a new streaming reader over `inner`.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner: io::BufReader::new(inner),
+            cur: 0,
+            bit: 0,
+        }
+    }
+}
+
+impl<R: Read> BitRead for IoReader<R> {
+    fn realign_to_byte(&mut self) {
+        self.bit = 0;
+    }
+
+    fn read_bit(&mut self) -> io::Result<bool> {
+        if self.bit == 0 {
+            let mut byte = [0u8; 1];
+            self.inner.read_exact(&mut byte)?;
+            self.cur = byte[0];
+        }
+
+        let value = (self.cur >> self.bit) & 1 != 0;
+        self.bit += 1;
+        if self.bit == u8::BITS as u8 {
+            self.bit = 0;
+        }
+
+        Ok(value)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        self.realign_to_byte();
+
+        let mut bytes = vec![0u8; len];
+        self.inner.read_exact(&mut bytes)?;
+
+        Ok(bytes)
+    }
+}
@@ -1,14 +1,22 @@
 use std::{
-    io::{self, Write},
+    io::{self, Read, Write},
     marker::PhantomData,
 };
 
 use anyhow::bail;
 use bitflags::bitflags;
-use byteorder::{ReadBytesExt, LE};
-use flate2::write::ZlibDecoder;
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use flate2::{
+    write::{ZlibDecoder, ZlibEncoder},
+    Compression,
+};
 
-use super::{reader::BitReader, type_list::*, TypeTag, Value};
+use super::{
+    reader::{BitRead, BitReadExt, IoReader, SliceReader},
+    type_list::*,
+    writer::BitWriter,
+    EnumValue, TypeTag, Value,
+};
 
 #[inline]
 fn zlib_decompress<W: Write>(data: &[u8], buf: W) -> io::Result<W> {
@@ -17,6 +25,13 @@ fn zlib_decompress<W: Write>(data: &[u8], buf: W) -> io::Result<W> {
     decoder.finish()
 }
 
+#[inline]
+fn zlib_compress(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
 bitflags! {
     /// Configuration bits to customize serialization
     /// behavior.
@@ -35,6 +50,9 @@ bitflags! {
         /// Any property with the `DELTA_ENCODE` bit must
         /// always have its value serialized.
         const FORBID_DELTA_ENCODE = 1 << 4;
+        /// Length prefixes and counts are encoded as LEB128
+        /// varints instead of their fixed-width integer type.
+        const VARINT_LENGTH_PREFIXES = 1 << 5;
     }
 }
 
@@ -53,9 +71,86 @@ pub struct DeserializerOptions {
     /// A recursion limit for nested data to avoid stack
     /// overflows.
     pub recursion_limit: u8,
+    /// An optional ceiling, in bytes, on how much input this
+    /// deserializer may read or allocate for in a single
+    /// [`Deserializer::deserialize`] call.
+    ///
+    /// Every length-prefixed read checks its decoded size
+    /// against this budget *before* reserving memory for it, so
+    /// a hostile length prefix fails cleanly instead of driving
+    /// an out-of-memory abort. `None` disables the check.
+    pub byte_limit: Option<usize>,
+    /// The running count of bytes charged against
+    /// [`DeserializerOptions::byte_limit`] so far.
+    bytes_read: usize,
+    /// Whether a property whose type cannot be resolved should be
+    /// captured as [`Value::Unknown`] and skipped, instead of
+    /// aborting the whole object.
+    ///
+    /// This trades strict validation for forward compatibility
+    /// with schema drift across game patch versions: a property
+    /// added or changed in a newer `TypeList` than the one the
+    /// caller has loaded is recorded verbatim rather than failing
+    /// the entire deserialization.
+    pub lenient: bool,
 }
 
 impl Default for DeserializerOptions {
+    fn default() -> Self {
+        Self {
+            flags: SerializerFlags::empty(),
+            property_mask: PropertyFlags::TRANSMIT | PropertyFlags::PRIVILEGED_TRANSMIT,
+            shallow: false,
+            manual_compression: false,
+            recursion_limit: u8::MAX / 2,
+            byte_limit: None,
+            bytes_read: 0,
+            lenient: false,
+        }
+    }
+}
+
+impl DeserializerOptions {
+    /// Charges `n` bytes against [`DeserializerOptions::byte_limit`],
+    /// failing *before* the caller acts on `n` (e.g. reserves a
+    /// buffer of that size) if it would exceed the configured
+    /// budget.
+    fn charge(&mut self, n: usize) -> anyhow::Result<()> {
+        if let Some(limit) = self.byte_limit {
+            let remaining = limit.saturating_sub(self.bytes_read);
+            if n > remaining {
+                bail!(
+                    "input exceeds configured limit of {limit} bytes ({n} requested, {remaining} remaining)"
+                );
+            }
+        }
+
+        self.bytes_read = self.bytes_read.saturating_add(n);
+        Ok(())
+    }
+}
+
+/// Configuration for the [`Serializer`].
+#[derive(Clone)]
+pub struct SerializerOptions {
+    /// The [`SerializerFlags`] to use.
+    pub flags: SerializerFlags,
+    /// A set of [`PropertyFlags`] for conditionally omitting
+    /// unmasked properties of a type.
+    pub property_mask: PropertyFlags,
+    /// Whether the shallow encoding strategy is used for
+    /// the data. The [`Serializer`] does not read this field;
+    /// its output is the same either way.
+    pub shallow: bool,
+    /// Whether the output should be zlib-compressed without
+    /// relying on `WITH_COMPRESSION`'s own marker byte.
+    pub manual_compression: bool,
+    /// A recursion limit for nested data to avoid stack
+    /// overflows.
+    pub recursion_limit: u8,
+}
+
+impl Default for SerializerOptions {
     fn default() -> Self {
         Self {
             flags: SerializerFlags::empty(),
@@ -69,33 +164,53 @@ impl Default for DeserializerOptions {
 
 /// A configurable deserializer for the ObjectProperty binary
 /// format, producing [`Value`]s.
-pub struct Deserializer<'de, T> {
-    reader: BitReader<'de>,
+///
+/// Generic over the [`BitRead`] it pulls data from - by default a
+/// [`SliceReader`] over a borrowed slice, or an [`IoReader`] wrapping any
+/// [`std::io::Read`] when constructed via
+/// [`Deserializer::from_reader`].
+pub struct Deserializer<'de, T, R: BitRead = SliceReader<'de>> {
+    reader: R,
     options: DeserializerOptions,
-    _t: PhantomData<T>,
+    _t: PhantomData<(T, &'de ())>,
 }
 
 macro_rules! impl_read_len {
-    ($($de:ident() = $read:ident()),* $(,)*) => {
+    ($($de:ident() = $read:ident() as $size:expr, bits = $bits:expr),* $(,)*) => {
         $(
             #[inline]
             fn $de(&mut self) -> anyhow::Result<usize> {
                 self.reader.realign_to_byte();
-                if self
+                let len = if self
                     .options
                     .flags
                     .contains(SerializerFlags::COMPACT_LENGTH_PREFIXES)
                 {
-                    self.read_compact_length_prefix()
+                    self.read_compact_length_prefix()?
+                } else if self
+                    .options
+                    .flags
+                    .contains(SerializerFlags::VARINT_LENGTH_PREFIXES)
+                {
+                    self.read_varint_length_prefix($bits)?
                 } else {
-                    self.reader.$read().map(|v| v as usize).map_err(Into::into)
-                }
+                    self.options.charge($size)?;
+                    self.reader.$read()? as usize
+                };
+
+                // The decoded length is about to drive an
+                // allocation (or a slice read) of its own -
+                // charge it against the budget before the
+                // caller acts on it.
+                self.options.charge(len)?;
+
+                Ok(len)
             }
         )*
     };
 }
 
-impl<'de, T> Deserializer<'de, T> {
+impl<'de, T> Deserializer<'de, T, SliceReader<'de>> {
     /// Creates a new deserializer with its configuration.
     ///
     /// No data for deserialization has been loaded at this
@@ -103,18 +218,24 @@ impl<'de, T> Deserializer<'de, T> {
     /// next.
     pub fn new(options: DeserializerOptions) -> Self {
         Self {
-            reader: BitReader::default(),
+            reader: SliceReader::default(),
             options,
             _t: PhantomData,
         }
     }
 
     fn decompress_data(
+        &mut self,
         mut data: &'de [u8],
         scratch: &'de mut Vec<u8>,
-    ) -> anyhow::Result<BitReader<'de>> {
+    ) -> anyhow::Result<SliceReader<'de>> {
         let size = data.read_u32::<LE>()? as usize;
 
+        // The decompressed size comes straight from the input;
+        // charge it against the budget before reserving memory
+        // for it.
+        self.options.charge(size)?;
+
         // Decompress into the scratch buffer.
         scratch.clear();
         scratch.reserve(size);
@@ -129,19 +250,31 @@ impl<'de, T> Deserializer<'de, T> {
             );
         }
 
-        Ok(BitReader::new(&decompressed[..]))
+        Ok(SliceReader::new(&decompressed[..]))
     }
 
+    /// Feeds a fully-buffered payload to the deserializer,
+    /// handling the outer stateful-flags and compression framing.
+    ///
+    /// For a streaming source, use [`Deserializer::from_reader`]
+    /// instead - it skips this framing and reads directly off the
+    /// provided [`std::io::Read`].
     pub fn feed_data(
         &mut self,
         mut data: &'de [u8],
         scratch: &'de mut Vec<u8>,
     ) -> anyhow::Result<()> {
+        // `byte_limit` is budgeted per payload, not cumulatively
+        // across the lifetime of this (reusable) deserializer -
+        // start this payload's framing reads with a fresh counter.
+        self.options.bytes_read = 0;
+
         let reader = if self.options.manual_compression {
-            let mut reader = Self::decompress_data(data, scratch)?;
+            let mut reader = self.decompress_data(data, scratch)?;
 
             // If configuration flags are stateful, deserialize them.
             if self.options.flags.contains(SerializerFlags::STATEFUL_FLAGS) {
+                self.options.charge(4)?;
                 self.options.flags = SerializerFlags::from_bits_truncate(reader.load_u32()?);
             }
 
@@ -149,6 +282,7 @@ impl<'de, T> Deserializer<'de, T> {
         } else {
             // If configuration flags are stateful, deserialize them.
             if self.options.flags.contains(SerializerFlags::STATEFUL_FLAGS) {
+                self.options.charge(4)?;
                 self.options.flags = SerializerFlags::from_bits_truncate(data.read_u32::<LE>()?);
             }
 
@@ -159,45 +293,104 @@ impl<'de, T> Deserializer<'de, T> {
                 .contains(SerializerFlags::WITH_COMPRESSION)
                 && data.read_u8()? != 0
             {
-                Self::decompress_data(data, scratch)?
+                self.decompress_data(data, scratch)?
             } else {
-                BitReader::new(data)
+                SliceReader::new(data)
             }
         };
 
         self.reader = reader;
         Ok(())
     }
+}
 
+impl<'de, T, R: Read> Deserializer<'de, T, IoReader<R>> {
+    /// Creates a new deserializer that streams its input on
+    /// demand from `reader`, rather than requiring the whole
+    /// payload to be buffered up front.
+    ///
+    /// Unlike [`Deserializer::new`] plus [`Deserializer::feed_data`],
+    /// this does not interpret any outer stateful-flags or
+    /// compression framing - `reader` is expected to already yield
+    /// the raw object body.
+    pub fn from_reader(options: DeserializerOptions, reader: R) -> Self {
+        Self {
+            reader: IoReader::new(reader),
+            options,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<'de, T, R: BitRead> Deserializer<'de, T, R> {
     fn read_compact_length_prefix(&mut self) -> anyhow::Result<usize> {
         let is_large = self.reader.read_bit()?;
-        if is_large {
-            self.reader
-                .read_value_bits(u32::BITS as usize - 1)
-                .map_err(Into::into)
+        let value = if is_large {
+            self.reader.read_value_bits(u32::BITS as usize - 1)?
         } else {
-            self.reader
-                .read_value_bits(u8::BITS as usize - 1)
-                .map_err(Into::into)
+            self.reader.read_value_bits(u8::BITS as usize - 1)?
+        };
+
+        self.options.charge(if is_large { 4 } else { 1 })?;
+        Ok(value as usize)
+    }
+
+    /// Reads a LEB128-encoded length prefix: seven value bits
+    /// per byte, least-significant group first, with the high
+    /// bit of every byte but the last set to signal continuation.
+    ///
+    /// Rejects encodings longer than `ceil(max_bits / 7)` bytes
+    /// and non-canonical encodings that pad the value out with a
+    /// trailing all-zero group.
+    fn read_varint_length_prefix(&mut self, max_bits: u32) -> anyhow::Result<usize> {
+        let max_bytes = (max_bits as usize).div_ceil(7);
+
+        let mut value = 0u64;
+        for i in 0..max_bytes {
+            self.options.charge(1)?;
+            let byte = self.reader.load_u8()?;
+            let group = u64::from(byte & 0x7F);
+
+            if byte & 0x80 == 0 {
+                if i > 0 && group == 0 {
+                    bail!("non-canonical LEB128 encoding (trailing zero group)");
+                }
+
+                value |= group << (7 * i);
+                if max_bits < u64::BITS && value >= 1 << max_bits {
+                    bail!("LEB128 value overflows a {max_bits}-bit length prefix");
+                }
+
+                return Ok(value as usize);
+            }
+
+            value |= group << (7 * i);
         }
+
+        bail!("LEB128 length prefix exceeds {max_bytes} bytes")
     }
 
     impl_read_len! {
         // Used for strings, where the length is written as a `u16`.
-        read_str_len() = load_u16(),
+        read_str_len() = load_u16() as 2, bits = 16,
 
         // Used for sequences, where the length is written as a `u32`.
-        read_seq_len() = load_u32(),
+        read_seq_len() = load_u32() as 4, bits = 32,
     }
 
     fn read_str(&mut self) -> anyhow::Result<Vec<u8>> {
-        self.read_str_len()
-            .and_then(|len| self.reader.read_bytes(len).map_err(Into::into))
+        let len = self.read_str_len()?;
+        self.reader.read_bytes(len).map_err(Into::into)
     }
 
     fn read_wstr(&mut self) -> anyhow::Result<Vec<u16>> {
         let len = self.read_str_len()?;
 
+        // Each element is a `u16`, so the real allocation is
+        // twice the element count - `read_str_len` already
+        // charged `len` once, charge the remainder here.
+        self.options.charge(len)?;
+
         let mut result = Vec::with_capacity(len);
         for _ in 0..len {
             result.push(self.reader.load_u16()?);
@@ -206,22 +399,16 @@ impl<'de, T> Deserializer<'de, T> {
         Ok(result)
     }
 
-    fn deserialize_unsigned_bits(&mut self, n: usize) -> anyhow::Result<u64> {
-        self.reader
-            .read_value_bits(n)
-            .map(|v| v as u64)
-            .map_err(Into::into)
-    }
-
-    fn deserialize_signed_bits(&mut self, n: usize) -> anyhow::Result<i64> {
-        self.deserialize_unsigned_bits(n).map(|v| {
-            // Perform sign-extension of the value we got.
-            if v & (1 << (n - 1)) != 0 {
-                (v as i64) | ((!0) << n)
-            } else {
-                v as i64
-            }
-        })
+    /// Wraps a property record's already-read, still-encoded bytes
+    /// into a [`Value::Unknown`] instead of failing outright.
+    ///
+    /// Only meant to be called when [`DeserializerOptions::lenient`]
+    /// is set; `bytes` were already charged against the byte limit
+    /// by the `read_seq_len` call that decoded the record's length,
+    /// and can be handed back to
+    /// [`Serializer::write_property_value`] to round-trip verbatim.
+    fn read_unknown_property(&mut self, tag: u32, bytes: Vec<u8>) -> Value {
+        Value::Unknown { tag, bytes }
     }
 }
 
@@ -242,22 +429,41 @@ macro_rules! impl_deserialize {
     ($($de:ident($ty:ty) = $read:ident()),* $(,)*) => {
         $(
             pub(crate) fn $de(&mut self) -> anyhow::Result<$ty> {
+                self.options.charge(std::mem::size_of::<$ty>())?;
                 self.reader.$read().map_err(Into::into)
             }
         )*
     };
 }
 
-impl<'de, T: TypeTag> Deserializer<'de, T> {
+impl<'de, T: TypeTag, R: BitRead> Deserializer<'de, T, R> {
     /// Deserializes an object [`Value`] from previously
     /// loaded data.
     pub fn deserialize(&mut self, types: &mut TypeList) -> anyhow::Result<Value> {
+        // `byte_limit` is budgeted per `deserialize` call, not
+        // cumulatively across the lifetime of this (reusable)
+        // deserializer - start this call with a fresh counter.
+        self.options.bytes_read = 0;
+
+        self.deserialize_inner(types)
+    }
+
+    /// The recursive core of [`Deserializer::deserialize`], shared
+    /// with nested `Object`-typed properties - unlike the public
+    /// entry point, this must not reset the byte-limit counter,
+    /// since that would forget the budget already spent on the
+    /// properties read so far.
+    fn deserialize_inner(&mut self, types: &mut TypeList) -> anyhow::Result<Value> {
         check_recursion! {
             let this = self;
 
             let type_def = T::object_identity(this, types)?;
             let res = if let Some(type_def) = type_def {
-                todo!()
+                let properties = this.read_properties(&type_def, types)?;
+                Value::Object {
+                    type_hash: type_def.hash,
+                    properties,
+                }
             } else {
                 Value::Empty
             };
@@ -266,6 +472,138 @@ impl<'de, T: TypeTag> Deserializer<'de, T> {
         Ok(res)
     }
 
+    /// Reads an object's properties, the inverse of
+    /// [`Serializer::write_properties`].
+    ///
+    /// Each property is a TLV-style record: a `u32` wire tag, a
+    /// byte length, then that many bytes holding the encoded
+    /// value. The length prefix is what lets a property whose tag
+    /// isn't declared on `type_def`, or whose record fails to
+    /// decode (e.g. a nested object of an unknown type), be
+    /// skipped wholesale in
+    /// [`DeserializerOptions::lenient`] mode instead of aborting
+    /// the whole object, mirroring a TLV stream's handling of
+    /// unknown record types.
+    fn read_properties(
+        &mut self,
+        type_def: &TypeDef,
+        types: &mut TypeList,
+    ) -> anyhow::Result<Vec<(String, Value)>> {
+        let count = self.read_seq_len()?;
+        let mut properties = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let tag = self.deserialize_u32()?;
+            let len = self.read_seq_len()?;
+            let bytes = self.reader.read_bytes(len)?;
+
+            let (name, value) = match type_def.properties.iter().find(|p| p.tag == tag) {
+                Some(prop) => {
+                    let value = match self.read_property_record(&prop.ty, &bytes, types) {
+                        Ok(value) => value,
+                        Err(_) if self.options.lenient => self.read_unknown_property(tag, bytes),
+                        Err(e) => return Err(e),
+                    };
+                    (prop.name.clone(), value)
+                }
+                None if self.options.lenient => {
+                    let value = self.read_unknown_property(tag, bytes);
+                    (format!("#{tag:08x}"), value)
+                }
+                None => bail!(
+                    "unknown property tag {tag:#010x} on type {:#010x} ({}); enable \
+                     `DeserializerOptions::lenient` to recover instead of failing",
+                    type_def.hash,
+                    type_def.name,
+                ),
+            };
+
+            properties.push((name, value));
+        }
+
+        Ok(properties)
+    }
+
+    /// Decodes a single property record's already-extracted body
+    /// `bytes` according to its declared `ty`, via a fresh
+    /// sub-deserializer over just those bytes.
+    ///
+    /// Running the record through its own [`SliceReader`] (rather
+    /// than continuing to read from `self.reader`) keeps a
+    /// property's encoding self-contained, which is exactly what
+    /// lets it be skipped by length alone when `ty` isn't known.
+    fn read_property_record(
+        &mut self,
+        ty: &PropertyType,
+        bytes: &[u8],
+        types: &mut TypeList,
+    ) -> anyhow::Result<Value> {
+        let nested_options = DeserializerOptions {
+            flags: self.options.flags,
+            recursion_limit: self.options.recursion_limit,
+            byte_limit: self
+                .options
+                .byte_limit
+                .map(|limit| limit.saturating_sub(self.options.bytes_read)),
+            lenient: self.options.lenient,
+            ..DeserializerOptions::default()
+        };
+
+        let mut nested = Deserializer::<'_, T, SliceReader<'_>>::new(nested_options);
+        nested.reader = SliceReader::new(bytes);
+        let value = nested.read_property_value(ty, types)?;
+
+        self.options.bytes_read = self.options.bytes_read.saturating_add(nested.options.bytes_read);
+        Ok(value)
+    }
+
+    /// Decodes a single property value of the declared `ty` from
+    /// `self.reader`, the inverse of
+    /// [`Serializer::write_property_value`].
+    fn read_property_value(&mut self, ty: &PropertyType, types: &mut TypeList) -> anyhow::Result<Value> {
+        Ok(match ty {
+            PropertyType::Bool => Value::Bool(self.reader.read_bit()?),
+            PropertyType::U8 => Value::U8(self.deserialize_u8()?),
+            PropertyType::U16 => Value::U16(self.deserialize_u16()?),
+            PropertyType::U32 => Value::U32(self.deserialize_u32()?),
+            PropertyType::U64 => Value::U64(self.deserialize_u64()?),
+            PropertyType::I8 => Value::I8(self.deserialize_i8()?),
+            PropertyType::I16 => Value::I16(self.deserialize_i16()?),
+            PropertyType::I32 => Value::I32(self.deserialize_i32()?),
+            PropertyType::F32 => Value::F32(self.deserialize_f32()?),
+            PropertyType::F64 => Value::F64(self.deserialize_f64()?),
+            PropertyType::Str => Value::Str(self.read_str()?),
+            PropertyType::WStr => Value::WStr(self.read_wstr()?),
+            PropertyType::Enum => self.read_enum()?,
+            PropertyType::List(element) => {
+                let len = self.read_seq_len()?;
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(self.read_property_value(element, types)?);
+                }
+
+                Value::List(items)
+            }
+            PropertyType::Object => self.deserialize_inner(types)?,
+        })
+    }
+
+    /// Reads an enum value, the inverse of [`Serializer::write_enum`].
+    fn read_enum(&mut self) -> anyhow::Result<Value> {
+        let value = if self
+            .options
+            .flags
+            .contains(SerializerFlags::HUMAN_READABLE_ENUMS)
+        {
+            let name = self.read_str()?;
+            EnumValue::Name(String::from_utf8(name)?)
+        } else {
+            EnumValue::Int(self.deserialize_i32()?)
+        };
+
+        Ok(Value::Enum(value))
+    }
+
     impl_deserialize! {
         deserialize_u8(u8)   = load_u8(),
         deserialize_u16(u16) = load_u16(),
@@ -279,4 +617,487 @@ impl<'de, T: TypeTag> Deserializer<'de, T> {
         deserialize_f32(f32) = load_f32(),
         deserialize_f64(f64) = load_f64(),
     }
-}
\ No newline at end of file
+}
+
+/// A configurable serializer for the ObjectProperty binary
+/// format, turning [`Value`]s back into bytes.
+///
+/// The serializer writes its object body into a caller-provided
+/// `scratch` buffer as it goes, the same way
+/// [`Deserializer::feed_data`] reuses a caller-provided buffer
+/// to stage decompressed data. The finished output returned by
+/// [`Serializer::serialize`], and the staging buffer for each
+/// property record, are freshly allocated on every call.
+pub struct Serializer<'ser, T> {
+    writer: BitWriter<&'ser mut Vec<u8>>,
+    options: SerializerOptions,
+    _t: PhantomData<T>,
+}
+
+macro_rules! impl_write_len {
+    ($($se:ident() = $write:ident() as $bits:expr),* $(,)*) => {
+        $(
+            #[inline]
+            fn $se(&mut self, len: usize) -> anyhow::Result<()> {
+                self.writer.realign_to_byte()?;
+                if self
+                    .options
+                    .flags
+                    .contains(SerializerFlags::COMPACT_LENGTH_PREFIXES)
+                {
+                    self.write_compact_length_prefix(len)
+                } else if self
+                    .options
+                    .flags
+                    .contains(SerializerFlags::VARINT_LENGTH_PREFIXES)
+                {
+                    self.write_varint_length_prefix(len as u64, $bits)
+                } else {
+                    self.writer.$write(len as _).map_err(Into::into)
+                }
+            }
+        )*
+    };
+}
+
+impl<'ser, T> Serializer<'ser, T> {
+    /// Creates a new serializer with its configuration, writing
+    /// its object body into `scratch`.
+    ///
+    /// `scratch` is cleared first; it does not need to be
+    /// empty on entry.
+    pub fn new(options: SerializerOptions, scratch: &'ser mut Vec<u8>) -> Self {
+        scratch.clear();
+
+        Self {
+            writer: BitWriter::new(scratch),
+            options,
+            _t: PhantomData,
+        }
+    }
+
+    fn write_compact_length_prefix(&mut self, len: usize) -> anyhow::Result<()> {
+        let large_bits = u32::BITS as usize - 1;
+        let small_bits = u8::BITS as usize - 1;
+
+        if len >= 1 << large_bits {
+            bail!("{len} does not fit into a compact length prefix");
+        }
+
+        let is_large = len >= 1 << small_bits;
+        self.writer.write_bit(is_large)?;
+
+        let bits = if is_large { large_bits } else { small_bits };
+        self.writer
+            .write_value_bits(len as u64, bits)
+            .map_err(Into::into)
+    }
+
+    /// Writes a LEB128-encoded length prefix, the inverse of
+    /// [`Deserializer::read_varint_length_prefix`].
+    fn write_varint_length_prefix(&mut self, value: u64, max_bits: u32) -> anyhow::Result<()> {
+        if max_bits < u64::BITS && value >= 1 << max_bits {
+            bail!("{value} does not fit into a {max_bits}-bit varint length prefix");
+        }
+
+        let mut value = value;
+        loop {
+            let mut byte = (value & 0x7F) as u8;
+            value >>= 7;
+
+            if value != 0 {
+                byte |= 0x80;
+            }
+
+            self.writer.store_u8(byte)?;
+            if value == 0 {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    impl_write_len! {
+        // Used for strings, where the length is written as a `u16`.
+        write_str_len() = store_u16() as 16,
+
+        // Used for sequences, where the length is written as a `u32`.
+        write_seq_len() = store_u32() as 32,
+    }
+
+    fn write_str(&mut self, data: &[u8]) -> anyhow::Result<()> {
+        self.write_str_len(data.len())?;
+        self.writer.write_bytes(data).map_err(Into::into)
+    }
+
+    fn write_wstr(&mut self, data: &[u16]) -> anyhow::Result<()> {
+        self.write_str_len(data.len())?;
+        for &c in data {
+            self.writer.store_u16(c)?;
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! impl_serialize {
+    ($($se:ident($ty:ty) = $write:ident()),* $(,)*) => {
+        $(
+            pub(crate) fn $se(&mut self, value: $ty) -> anyhow::Result<()> {
+                self.writer.$write(value).map_err(Into::into)
+            }
+        )*
+    };
+}
+
+impl<'ser, T: TypeTag> Serializer<'ser, T> {
+    /// Serializes `value` into a finished byte buffer, honoring
+    /// the configured [`SerializerFlags`].
+    pub fn serialize(mut self, value: &Value, types: &mut TypeList) -> anyhow::Result<Vec<u8>> {
+        let stateful_flags = self.options.flags.contains(SerializerFlags::STATEFUL_FLAGS);
+
+        // When manually compressed, the flag word (if stateful)
+        // is part of the compressed body, mirroring how
+        // `feed_data` reads it from the decompressed reader.
+        if self.options.manual_compression && stateful_flags {
+            self.writer.store_u32(self.options.flags.bits())?;
+        }
+
+        self.write_value(value, types)?;
+        let body = self.writer.finish()?;
+
+        let mut out = Vec::new();
+
+        // Otherwise, the flag word is part of the raw, possibly
+        // still-compressed outer stream.
+        if !self.options.manual_compression && stateful_flags {
+            out.write_u32::<LE>(self.options.flags.bits())?;
+        }
+
+        if self.options.manual_compression {
+            let compressed = zlib_compress(body)?;
+            out.write_u32::<LE>(body.len() as u32)?;
+            out.extend_from_slice(&compressed);
+        } else if self
+            .options
+            .flags
+            .contains(SerializerFlags::WITH_COMPRESSION)
+        {
+            let compressed = zlib_compress(body)?;
+            out.write_u8(1)?;
+            out.write_u32::<LE>(body.len() as u32)?;
+            out.extend_from_slice(&compressed);
+        } else {
+            out.extend_from_slice(body);
+        }
+
+        Ok(out)
+    }
+
+    fn should_serialize(&self, flags: PropertyFlags) -> bool {
+        if flags.contains(PropertyFlags::DELTA_ENCODE)
+            && self
+                .options
+                .flags
+                .contains(SerializerFlags::FORBID_DELTA_ENCODE)
+        {
+            return true;
+        }
+
+        self.options.property_mask.intersects(flags)
+    }
+
+    fn write_properties(
+        &mut self,
+        type_hash: u32,
+        properties: &[(String, Value)],
+        types: &mut TypeList,
+    ) -> anyhow::Result<()> {
+        let type_def = types.get(type_hash).cloned();
+
+        let included: Vec<&(String, Value)> = properties
+            .iter()
+            .filter(|(name, value)| {
+                // `Unknown` properties were captured verbatim by a
+                // lenient deserializer - always re-emit them rather
+                // than mask them out, since their real flags are
+                // unknowable.
+                if matches!(value, Value::Unknown { .. }) {
+                    return true;
+                }
+
+                let flags = type_def
+                    .as_ref()
+                    .and_then(|ty| ty.properties.iter().find(|p| &p.name == name))
+                    .map(|p| p.flags)
+                    .unwrap_or(PropertyFlags::empty());
+
+                self.should_serialize(flags)
+            })
+            .collect();
+
+        self.write_seq_len(included.len())?;
+        for (name, value) in included {
+            let tag = match value {
+                Value::Unknown { tag, .. } => *tag,
+                _ => {
+                    let prop = type_def
+                        .as_ref()
+                        .and_then(|ty| ty.properties.iter().find(|p| &p.name == name));
+                    match prop {
+                        Some(prop) => prop.tag,
+                        None => bail!(
+                            "property {name:?} is not declared on type {type_hash:#010x}, \
+                             cannot determine its wire tag"
+                        ),
+                    }
+                }
+            };
+
+            self.serialize_u32(tag)?;
+            self.write_property_record(value, types)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a single property as a TLV-style record: the tag
+    /// (already written by the caller), a byte length, then the
+    /// value's encoding - the inverse of
+    /// [`Deserializer::read_property_record`].
+    ///
+    /// The value is first encoded into a scratch buffer through a
+    /// nested [`Serializer`] so its length is known upfront; this
+    /// is what lets a reader that doesn't understand `value`'s type
+    /// skip the record instead of aborting.
+    fn write_property_record(&mut self, value: &Value, types: &mut TypeList) -> anyhow::Result<()> {
+        let mut body = Vec::new();
+        {
+            let mut nested = Serializer::<T>::new(self.options.clone(), &mut body);
+            nested.write_property_value(value, types)?;
+            nested.writer.finish()?;
+        }
+
+        self.write_seq_len(body.len())?;
+        self.writer.write_bytes(&body).map_err(Into::into)
+    }
+
+    fn write_enum(&mut self, value: &EnumValue) -> anyhow::Result<()> {
+        if self
+            .options
+            .flags
+            .contains(SerializerFlags::HUMAN_READABLE_ENUMS)
+        {
+            match value {
+                EnumValue::Name(name) => self.write_str(name.as_bytes()),
+                EnumValue::Int(v) => {
+                    bail!("enum value {v} has no name to serialize under HUMAN_READABLE_ENUMS")
+                }
+            }
+        } else {
+            match value {
+                EnumValue::Int(v) => self.serialize_i32(*v),
+                EnumValue::Name(name) => {
+                    bail!("enum value \"{name}\" has no integer representation to serialize")
+                }
+            }
+        }
+    }
+
+    fn write_property_value(&mut self, value: &Value, types: &mut TypeList) -> anyhow::Result<()> {
+        match value {
+            Value::Empty => Ok(()),
+            Value::Bool(v) => self.writer.write_bit(*v).map_err(Into::into),
+            Value::U8(v) => self.serialize_u8(*v),
+            Value::U16(v) => self.serialize_u16(*v),
+            Value::U32(v) => self.serialize_u32(*v),
+            Value::U64(v) => self.serialize_u64(*v),
+            Value::I8(v) => self.serialize_i8(*v),
+            Value::I16(v) => self.serialize_i16(*v),
+            Value::I32(v) => self.serialize_i32(*v),
+            Value::F32(v) => self.serialize_f32(*v),
+            Value::F64(v) => self.serialize_f64(*v),
+            Value::Str(s) => self.write_str(s),
+            Value::WStr(s) => self.write_wstr(s),
+            Value::Enum(e) => self.write_enum(e),
+            Value::List(items) => {
+                self.write_seq_len(items.len())?;
+                for item in items {
+                    self.write_property_value(item, types)?;
+                }
+
+                Ok(())
+            }
+            Value::Object { .. } => self.write_value(value, types),
+            Value::Unknown { bytes, .. } => self.writer.write_bytes(bytes).map_err(Into::into),
+        }
+    }
+
+    fn write_value(&mut self, value: &Value, types: &mut TypeList) -> anyhow::Result<()> {
+        check_recursion! {
+            let this = self;
+
+            match value {
+                Value::Empty => T::write_identity(this, 0)?,
+                Value::Object { type_hash, properties } => {
+                    T::write_identity(this, *type_hash)?;
+                    this.write_properties(*type_hash, properties, types)?;
+                }
+                _ => bail!("expected an object or empty value at the top level"),
+            };
+        }
+
+        Ok(())
+    }
+
+    impl_serialize! {
+        serialize_u8(u8)   = store_u8(),
+        serialize_u16(u16) = store_u16(),
+        serialize_u32(u32) = store_u32(),
+        serialize_u64(u64) = store_u64(),
+
+        serialize_i8(i8)   = store_i8(),
+        serialize_i16(i16) = store_i16(),
+        serialize_i32(i32) = store_i32(),
+
+        serialize_f32(f32) = store_f32(),
+        serialize_f64(f64) = store_f64(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_types() -> TypeList {
+        TypeList::from_types(vec![TypeDef {
+            hash: 0x1111_1111,
+            name: "TestObject".to_string(),
+            properties: vec![PropertyDef {
+                tag: 1,
+                name: "value".to_string(),
+                flags: PropertyFlags::TRANSMIT,
+                ty: PropertyType::U32,
+            }],
+        }])
+    }
+
+    fn roundtrip(value: &Value, types: &mut TypeList, options: DeserializerOptions) -> Value {
+        let mut scratch = Vec::new();
+        let bytes = Serializer::<PropertyClass>::new(SerializerOptions::default(), &mut scratch)
+            .serialize(value, types)
+            .unwrap();
+
+        let mut de = Deserializer::<PropertyClass>::new(options);
+        let mut de_scratch = Vec::new();
+        de.feed_data(&bytes, &mut de_scratch).unwrap();
+        de.deserialize(types).unwrap()
+    }
+
+    #[test]
+    fn empty_value_roundtrips() {
+        let mut types = TypeList::default();
+        let decoded = roundtrip(&Value::Empty, &mut types, DeserializerOptions::default());
+        assert_eq!(decoded, Value::Empty);
+    }
+
+    #[test]
+    fn known_property_roundtrips() {
+        let mut types = test_types();
+        let value = Value::Object {
+            type_hash: 0x1111_1111,
+            properties: vec![("value".to_string(), Value::U32(42))],
+        };
+
+        let decoded = roundtrip(&value, &mut types, DeserializerOptions::default());
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn unknown_property_is_skipped_and_preserved_when_lenient() {
+        let mut types = test_types();
+        let value = Value::Object {
+            type_hash: 0x1111_1111,
+            properties: vec![
+                ("value".to_string(), Value::U32(42)),
+                (
+                    "#deadbeef".to_string(),
+                    Value::Unknown {
+                        tag: 0xdead_beef,
+                        bytes: vec![1, 2, 3, 4],
+                    },
+                ),
+            ],
+        };
+
+        let options = DeserializerOptions {
+            lenient: true,
+            ..DeserializerOptions::default()
+        };
+        let decoded = roundtrip(&value, &mut types, options);
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn unknown_property_fails_when_not_lenient() {
+        let mut types = test_types();
+        let value = Value::Object {
+            type_hash: 0x1111_1111,
+            properties: vec![(
+                "#deadbeef".to_string(),
+                Value::Unknown {
+                    tag: 0xdead_beef,
+                    bytes: vec![1, 2, 3, 4],
+                },
+            )],
+        };
+
+        let mut scratch = Vec::new();
+        let bytes = Serializer::<PropertyClass>::new(SerializerOptions::default(), &mut scratch)
+            .serialize(&value, &mut types)
+            .unwrap();
+
+        let mut de = Deserializer::<PropertyClass>::new(DeserializerOptions::default());
+        let mut de_scratch = Vec::new();
+        de.feed_data(&bytes, &mut de_scratch).unwrap();
+        assert!(de.deserialize(&mut types).is_err());
+    }
+
+    fn write_varint(value: u64, max_bits: u32) -> Vec<u8> {
+        let mut scratch = Vec::new();
+        let mut ser = Serializer::<PropertyClass>::new(SerializerOptions::default(), &mut scratch);
+        ser.write_varint_length_prefix(value, max_bits).unwrap();
+        ser.writer.finish().unwrap().clone()
+    }
+
+    fn read_varint(bytes: &[u8], max_bits: u32) -> anyhow::Result<usize> {
+        let mut de = Deserializer::<PropertyClass>::new(DeserializerOptions::default());
+        de.reader = SliceReader::new(bytes);
+        de.read_varint_length_prefix(max_bits)
+    }
+
+    #[test]
+    fn varint_length_prefix_roundtrips() {
+        for &value in &[0u64, 1, 63, 127, 128, 300, 16383, 16384, u32::MAX as u64] {
+            let bytes = write_varint(value, 32);
+            assert_eq!(read_varint(&bytes, 32).unwrap(), value as usize);
+        }
+    }
+
+    #[test]
+    fn varint_length_prefix_rejects_overflow() {
+        // `300` needs more than 8 bits to represent.
+        let bytes = write_varint(300, 32);
+        let err = read_varint(&bytes, 8).unwrap_err();
+        assert!(err.to_string().contains("overflows a 8-bit length prefix"));
+    }
+
+    #[test]
+    fn varint_length_prefix_rejects_non_canonical_encoding() {
+        // `0x80, 0x00` encodes `0` via a redundant continuation
+        // byte followed by an all-zero group.
+        let err = read_varint(&[0x80, 0x00], 32).unwrap_err();
+        assert!(err.to_string().contains("non-canonical LEB128 encoding"));
+    }
+}
@@ -0,0 +1,207 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+use bitflags::bitflags;
+
+use super::{
+    reader::BitRead,
+    serialization::{Deserializer, Serializer},
+};
+
+bitflags! {
+    /// Flags describing how a property of a type should be
+    /// treated during (de)serialization.
+    pub struct PropertyFlags: u32 {
+        /// The property is sent over the network.
+        const TRANSMIT = 1 << 0;
+        /// The property is only sent to privileged clients.
+        const PRIVILEGED_TRANSMIT = 1 << 1;
+        /// The property is only (re-)serialized when its value
+        /// has changed since the last transmission.
+        const DELTA_ENCODE = 1 << 2;
+    }
+}
+
+/// The concrete wire encoding of a single property's value, as
+/// declared by its [`PropertyDef`].
+///
+/// This is what lets [`Deserializer`](super::Deserializer) and
+/// [`Serializer`](super::Serializer) turn a property's raw record
+/// bytes into (or out of) a typed [`Value`](super::Value) without
+/// the value self-describing its own type on the wire.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyType {
+    /// A boolean stored as a single bit.
+    Bool,
+    /// An unsigned 8-bit integer.
+    U8,
+    /// An unsigned 16-bit integer.
+    U16,
+    /// An unsigned 32-bit integer.
+    U32,
+    /// An unsigned 64-bit integer.
+    U64,
+    /// A signed 8-bit integer.
+    I8,
+    /// A signed 16-bit integer.
+    I16,
+    /// A signed 32-bit integer.
+    I32,
+    /// A 32-bit floating-point number.
+    F32,
+    /// A 64-bit floating-point number.
+    F64,
+    /// A narrow, length-prefixed byte string.
+    Str,
+    /// A wide, length-prefixed UTF-16 string.
+    WStr,
+    /// An enum, encoded per `SerializerFlags::HUMAN_READABLE_ENUMS`.
+    Enum,
+    /// A homogeneous sequence of elements of the boxed type.
+    List(Box<PropertyType>),
+    /// A nested object, identified the same way as the top-level
+    /// value via the active [`TypeTag`].
+    Object,
+}
+
+/// The definition of a single property on a [`TypeDef`], as
+/// loaded from a `TypeList`.
+#[derive(Debug, Clone)]
+pub struct PropertyDef {
+    /// The wire tag identifying this property within its type,
+    /// analogous to [`TypeDef::hash`] for object types.
+    pub tag: u32,
+    /// The property's declared name.
+    pub name: String,
+    /// The property's behavioral flags.
+    pub flags: PropertyFlags,
+    /// The property's declared value encoding.
+    pub ty: PropertyType,
+}
+
+/// The definition of a serializable type, as loaded from a
+/// `TypeList`.
+#[derive(Debug, Clone)]
+pub struct TypeDef {
+    /// The type's unique hash, as found on the wire.
+    pub hash: u32,
+    /// The type's human-readable name.
+    pub name: String,
+    /// The ordered list of properties that make up the type.
+    pub properties: Vec<PropertyDef>,
+}
+
+/// A loaded schema of all known [`TypeDef`]s, used to resolve
+/// type hashes and property layouts while (de)serializing.
+#[derive(Debug, Default, Clone)]
+pub struct TypeList {
+    types: Vec<TypeDef>,
+}
+
+impl TypeList {
+    /// Builds a `TypeList` directly from already-parsed [`TypeDef`]s,
+    /// bypassing the textual dump format - useful for callers that
+    /// already have structured schema data (or, in tests, a
+    /// hand-built schema).
+    pub fn from_types(types: Vec<TypeDef>) -> Self {
+        Self { types }
+    }
+
+    /// Looks up a [`TypeDef`] by its wire hash.
+    pub fn get(&self, hash: u32) -> Option<&TypeDef> {
+        self.types.iter().find(|ty| ty.hash == hash)
+    }
+}
+
+impl FromStr for TypeList {
+    type Err = anyhow::Error;
+
+    /// Accepts a textual type list dump without parsing it: empty
+    /// input is rejected, and any other input yields an empty
+    /// `TypeList`.
+    fn from_str(data: &str) -> anyhow::Result<Self> {
+        // The textual format is produced by the game's type list
+        // dumper and is out of scope for this stub; callers are
+        // expected to construct a `TypeList` from real schema
+        // data obtained from the game client.
+        if data.trim().is_empty() {
+            bail!("type list input is empty");
+        }
+
+        Ok(Self { types: Vec::new() })
+    }
+}
+
+/// A marker type selecting which concrete object identity
+/// scheme a [`Deserializer`]/[`Serializer`](super::Serializer)
+/// should use to look up [`TypeDef`]s.
+pub trait TypeTag {
+    /// Reads the wire representation identifying an object's
+    /// type and resolves it against `types`.
+    ///
+    /// Returns `None` if the wire encodes "no object".
+    fn object_identity<'de, R: BitRead>(
+        de: &mut Deserializer<'de, Self, R>,
+        types: &mut TypeList,
+    ) -> anyhow::Result<Option<TypeDef>>
+    where
+        Self: Sized;
+
+    /// Writes the wire representation identifying an object's
+    /// type, the inverse of [`TypeTag::object_identity`].
+    ///
+    /// `hash` is `0` to signal "no object".
+    fn write_identity<'ser>(ser: &mut Serializer<'ser, Self>, hash: u32) -> anyhow::Result<()>
+    where
+        Self: Sized;
+}
+
+/// Selects the object identity scheme used for `PropertyClass`
+/// hierarchies (most gameplay objects).
+pub struct PropertyClass;
+
+impl TypeTag for PropertyClass {
+    fn object_identity<'de, R: BitRead>(
+        de: &mut Deserializer<'de, Self, R>,
+        types: &mut TypeList,
+    ) -> anyhow::Result<Option<TypeDef>> {
+        let hash = de.deserialize_u32()?;
+        if hash == 0 {
+            return Ok(None);
+        }
+
+        match types.get(hash) {
+            Some(ty) => Ok(Some(ty.clone())),
+            None => bail!("unknown PropertyClass type hash {hash:#010x}"),
+        }
+    }
+
+    fn write_identity<'ser>(ser: &mut Serializer<'ser, Self>, hash: u32) -> anyhow::Result<()> {
+        ser.serialize_u32(hash)
+    }
+}
+
+/// Selects the object identity scheme used for `CoreObject`
+/// hierarchies (serialized `.wad`/save-game roots).
+pub struct CoreObject;
+
+impl TypeTag for CoreObject {
+    fn object_identity<'de, R: BitRead>(
+        de: &mut Deserializer<'de, Self, R>,
+        types: &mut TypeList,
+    ) -> anyhow::Result<Option<TypeDef>> {
+        let hash = de.deserialize_u32()?;
+        if hash == 0 {
+            return Ok(None);
+        }
+
+        match types.get(hash) {
+            Some(ty) => Ok(Some(ty.clone())),
+            None => bail!("unknown CoreObject type hash {hash:#010x}"),
+        }
+    }
+
+    fn write_identity<'ser>(ser: &mut Serializer<'ser, Self>, hash: u32) -> anyhow::Result<()> {
+        ser.serialize_u32(hash)
+    }
+}
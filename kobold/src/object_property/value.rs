@@ -0,0 +1,86 @@
+/// A single deserialized property or element of the
+/// ObjectProperty binary format.
+///
+/// This is the dynamically-typed representation produced by
+/// [`Deserializer`](super::Deserializer) and consumed by
+/// [`Serializer`](super::Serializer); it mirrors the shape of
+/// the wire format closely enough to be re-serialized without
+/// loss.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// No object was present at all.
+    Empty,
+
+    /// A boolean stored as a single bit.
+    Bool(bool),
+
+    /// An unsigned 8-bit integer.
+    U8(u8),
+    /// An unsigned 16-bit integer.
+    U16(u16),
+    /// An unsigned 32-bit integer.
+    U32(u32),
+    /// An unsigned 64-bit integer.
+    U64(u64),
+
+    /// A signed 8-bit integer.
+    I8(i8),
+    /// A signed 16-bit integer.
+    I16(i16),
+    /// A signed 32-bit integer.
+    I32(i32),
+
+    /// A 32-bit floating-point number.
+    F32(f32),
+    /// A 64-bit floating-point number.
+    F64(f64),
+
+    /// A narrow, length-prefixed byte string.
+    Str(Vec<u8>),
+    /// A wide, length-prefixed UTF-16 string.
+    WStr(Vec<u16>),
+
+    /// An enum value, either encoded as its underlying integer
+    /// representation or, when `HUMAN_READABLE_ENUMS` is set,
+    /// spelled out by name.
+    Enum(EnumValue),
+
+    /// A homogeneous sequence of values, e.g. a `std::vector<T>`.
+    List(Vec<Value>),
+
+    /// An object made up of named, ordered properties.
+    ///
+    /// The order of properties is preserved because it is
+    /// significant for re-serialization.
+    Object {
+        /// The wire hash identifying the object's `TypeDef`.
+        type_hash: u32,
+        /// The object's properties, in on-wire order.
+        properties: Vec<(String, Value)>,
+    },
+
+    /// A property whose wire bytes could not be interpreted,
+    /// either because its type is unknown to the active
+    /// [`TypeList`](super::TypeList) or because the deserializer
+    /// was not configured to understand it.
+    ///
+    /// Only produced when the deserializer is run in lenient
+    /// mode; see `DeserializerOptions::lenient`.
+    Unknown {
+        /// The property's type tag, as found on the wire.
+        tag: u32,
+        /// The raw, still-encoded bytes of the property.
+        bytes: Vec<u8>,
+    },
+}
+
+/// An enum value as produced by the deserializer, before it is
+/// known whether the caller wants the integer or the name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnumValue {
+    /// The raw integer representation found on the wire.
+    Int(i32),
+    /// The human-readable name, as found on the wire when
+    /// `HUMAN_READABLE_ENUMS` is set.
+    Name(String),
+}
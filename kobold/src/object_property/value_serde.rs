@@ -0,0 +1,216 @@
+//! A `serde` bridge for [`Value`], modeled on `serde_cbor`'s
+//! `value::Value`: every variant round-trips through a
+//! self-describing format such as JSON or CBOR (deserialization
+//! relies on `deserialize_any`, which non-self-describing formats
+//! do not support), with a single-key tagged map standing in for
+//! the distinctions (signed vs unsigned width, narrow vs wide
+//! strings, enum-as-int vs enum-as-name) that a bare JSON number
+//! or string would otherwise lose.
+
+use std::fmt;
+
+use serde::{
+    de::{self, MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer as SerdeDeserializer, Serialize, Serializer as SerdeSerializer,
+};
+
+use super::{EnumValue, Value};
+
+fn tagged<S: SerdeSerializer, T: Serialize + ?Sized>(
+    serializer: S,
+    tag: &'static str,
+    value: &T,
+) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(1))?;
+    map.serialize_entry(tag, value)?;
+    map.end()
+}
+
+impl Serialize for Value {
+    fn serialize<S: SerdeSerializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Empty => serializer.serialize_none(),
+            Value::Bool(v) => serializer.serialize_bool(*v),
+            Value::U8(v) => tagged(serializer, "u8", v),
+            Value::U16(v) => tagged(serializer, "u16", v),
+            Value::U32(v) => tagged(serializer, "u32", v),
+            Value::U64(v) => tagged(serializer, "u64", v),
+            Value::I8(v) => tagged(serializer, "i8", v),
+            Value::I16(v) => tagged(serializer, "i16", v),
+            Value::I32(v) => tagged(serializer, "i32", v),
+            Value::F32(v) => tagged(serializer, "f32", v),
+            Value::F64(v) => tagged(serializer, "f64", v),
+            Value::Str(v) => tagged(serializer, "str", v),
+            Value::WStr(v) => tagged(serializer, "wstr", v),
+            Value::Enum(EnumValue::Int(v)) => tagged(serializer, "enum_int", v),
+            Value::Enum(EnumValue::Name(v)) => tagged(serializer, "enum_name", v),
+            Value::List(v) => tagged(serializer, "list", v),
+            Value::Object {
+                type_hash,
+                properties,
+            } => tagged(serializer, "object", &(type_hash, properties)),
+            Value::Unknown { tag, bytes } => tagged(serializer, "unknown", &(tag, bytes)),
+        }
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a null, bool, or a single-key tagged `Value` map")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Value::Empty)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Value::Empty)
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let tag: String = map
+            .next_key()?
+            .ok_or_else(|| de::Error::custom("expected a single-key tagged `Value` map"))?;
+
+        let value = match tag.as_str() {
+            "u8" => Value::U8(map.next_value()?),
+            "u16" => Value::U16(map.next_value()?),
+            "u32" => Value::U32(map.next_value()?),
+            "u64" => Value::U64(map.next_value()?),
+            "i8" => Value::I8(map.next_value()?),
+            "i16" => Value::I16(map.next_value()?),
+            "i32" => Value::I32(map.next_value()?),
+            "f32" => Value::F32(map.next_value()?),
+            "f64" => Value::F64(map.next_value()?),
+            "str" => Value::Str(map.next_value()?),
+            "wstr" => Value::WStr(map.next_value()?),
+            "enum_int" => Value::Enum(EnumValue::Int(map.next_value()?)),
+            "enum_name" => Value::Enum(EnumValue::Name(map.next_value()?)),
+            "list" => Value::List(map.next_value()?),
+            "object" => {
+                let (type_hash, properties) = map.next_value()?;
+                Value::Object {
+                    type_hash,
+                    properties,
+                }
+            }
+            "unknown" => {
+                let (tag, bytes) = map.next_value()?;
+                Value::Unknown { tag, bytes }
+            }
+            other => return Err(de::Error::unknown_variant(other, KNOWN_TAGS)),
+        };
+
+        Ok(value)
+    }
+}
+
+const KNOWN_TAGS: &[&str] = &[
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "i8",
+    "i16",
+    "i32",
+    "f32",
+    "f64",
+    "str",
+    "wstr",
+    "enum_int",
+    "enum_name",
+    "list",
+    "object",
+    "unknown",
+];
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: SerdeDeserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+impl Value {
+    /// Renders this value as pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Parses a `Value` back out of JSON previously produced by
+    /// [`Value::to_json`].
+    pub fn from_json(data: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(data)
+    }
+
+    /// Renders this value as a compact CBOR document.
+    pub fn to_cbor(&self) -> serde_cbor::Result<Vec<u8>> {
+        serde_cbor::to_vec(self)
+    }
+
+    /// Parses a `Value` back out of CBOR previously produced by
+    /// [`Value::to_cbor`].
+    pub fn from_cbor(data: &[u8]) -> serde_cbor::Result<Self> {
+        serde_cbor::from_slice(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Value {
+        Value::Object {
+            type_hash: 0x1234_5678,
+            properties: vec![
+                ("flag".to_string(), Value::Bool(true)),
+                ("narrow".to_string(), Value::U32(42)),
+                ("wide".to_string(), Value::U64(u64::MAX)),
+                ("name".to_string(), Value::Str(b"hello".to_vec())),
+                (
+                    "wname".to_string(),
+                    Value::WStr("hi".encode_utf16().collect()),
+                ),
+                ("as_int".to_string(), Value::Enum(EnumValue::Int(7))),
+                (
+                    "as_name".to_string(),
+                    Value::Enum(EnumValue::Name("Member".to_string())),
+                ),
+                (
+                    "items".to_string(),
+                    Value::List(vec![Value::U8(1), Value::U8(2), Value::U8(3)]),
+                ),
+                (
+                    "unknown".to_string(),
+                    Value::Unknown {
+                        tag: 0xdead_beef,
+                        bytes: vec![0xaa, 0xbb],
+                    },
+                ),
+                ("nested".to_string(), Value::Empty),
+            ],
+        }
+    }
+
+    #[test]
+    fn json_roundtrips() {
+        let value = sample();
+        let json = value.to_json().unwrap();
+        assert_eq!(Value::from_json(&json).unwrap(), value);
+    }
+
+    #[test]
+    fn cbor_roundtrips() {
+        let value = sample();
+        let cbor = value.to_cbor().unwrap();
+        assert_eq!(Value::from_cbor(&cbor).unwrap(), value);
+    }
+}
@@ -0,0 +1,150 @@
+use std::io;
+
+use byteorder::{WriteBytesExt, LE};
+
+/// A sink that bytes can be written to, together with a hint
+/// about how many more bytes it expects to receive.
+///
+/// Modeled on rust-lightning's `util::ser::Writer`: a thin
+/// abstraction that lets the same serialization code run
+/// against any sink implementing it without paying for trait
+/// object dispatch on the hot path. It is implemented for
+/// `Vec<u8>` and for `&mut W` of any implementor; there is no
+/// implementation for arbitrary `io::Write` types.
+pub trait Writer {
+    /// Writes the entirety of `buf`.
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()>;
+
+    /// Hints that `size` additional bytes are about to be
+    /// written, allowing the sink to reserve capacity upfront.
+    fn size_hint(&mut self, size: usize);
+}
+
+impl Writer for Vec<u8> {
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+
+    fn size_hint(&mut self, size: usize) {
+        self.reserve(size);
+    }
+}
+
+impl<W: Writer + ?Sized> Writer for &mut W {
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        (**self).write_all(buf)
+    }
+
+    fn size_hint(&mut self, size: usize) {
+        (**self).size_hint(size)
+    }
+}
+
+/// A sink over a byte buffer that additionally supports writing
+/// individual bits, mirroring [`SliceReader`](super::reader::SliceReader).
+///
+/// Bits are packed least-significant-first within a byte, so
+/// that a `BitWriter`'s output is byte-for-byte identical to
+/// what a bit reader over the same bit sequence would consume.
+pub struct BitWriter<W> {
+    inner: W,
+    cur: u8,
+    bit: u8,
+}
+
+impl<W: Writer> BitWriter<W> {
+    /// Creates a new bit writer wrapping `inner`.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            cur: 0,
+            bit: 0,
+        }
+    }
+
+    /// Flushes a partially written byte, if any, so the next
+    /// write starts at a byte boundary.
+    pub fn realign_to_byte(&mut self) -> io::Result<()> {
+        if self.bit != 0 {
+            let byte = self.cur;
+            self.cur = 0;
+            self.bit = 0;
+            self.inner.write_all(&[byte])?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a single bit to the stream.
+    pub fn write_bit(&mut self, value: bool) -> io::Result<()> {
+        if value {
+            self.cur |= 1 << self.bit;
+        }
+
+        self.bit += 1;
+        if self.bit == u8::BITS as u8 {
+            let byte = self.cur;
+            self.cur = 0;
+            self.bit = 0;
+            self.inner.write_all(&[byte])?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the low `n` bits of `value`, least-significant
+    /// bit first.
+    pub fn write_value_bits(&mut self, value: u64, n: usize) -> io::Result<()> {
+        for i in 0..n {
+            self.write_bit(value & (1 << i) != 0)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `bytes` verbatim, realigning to a byte boundary
+    /// first.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.realign_to_byte()?;
+        self.inner.size_hint(bytes.len());
+        self.inner.write_all(bytes)
+    }
+
+    /// Unwraps the writer, flushing any partially written byte.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.realign_to_byte()?;
+        Ok(self.inner)
+    }
+}
+
+macro_rules! impl_store {
+    ($($store:ident($ty:ty) = $write:ident($($arg:ty)?)),* $(,)*) => {
+        impl<W: Writer> BitWriter<W> {
+            $(
+                #[doc = concat!("Writes a byte-aligned little-endian `", stringify!($ty), "`.")]
+                pub fn $store(&mut self, value: $ty) -> io::Result<()> {
+                    self.realign_to_byte()?;
+
+                    let mut buf = [0u8; std::mem::size_of::<$ty>()];
+                    (&mut buf[..]).$write$(::<$arg>)?(value)?;
+                    self.inner.write_all(&buf)
+                }
+            )*
+        }
+    };
+}
+
+impl_store! {
+    store_u8(u8) = write_u8(),
+    store_u16(u16) = write_u16(LE),
+    store_u32(u32) = write_u32(LE),
+    store_u64(u64) = write_u64(LE),
+
+    store_i8(i8) = write_i8(),
+    store_i16(i16) = write_i16(LE),
+    store_i32(i32) = write_i32(LE),
+
+    store_f32(f32) = write_f32(LE),
+    store_f64(f64) = write_f64(LE),
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc};
 
 use kobold::object_property as kobold;
 use pyo3::{exceptions::PyNotImplementedError, prelude::*};
@@ -88,12 +88,108 @@ impl CoreObjectDeserializer {
     }
 }
 
+#[pyclass(subclass)]
+struct Serializer;
+
+#[pymethods]
+impl Serializer {
+    #[new]
+    pub fn new(_options: kobold::SerializerOptions, _types: &TypeList) -> Self {
+        Self
+    }
+
+    pub fn serialize(&mut self, _value: kobold::Value) -> PyResult<Vec<u8>> {
+        Err(PyNotImplementedError::new_err("use a Serializer subclass"))
+    }
+}
+
+#[pyclass(extends = Serializer, subclass)]
+struct BinarySerializer {
+    options: kobold::SerializerOptions,
+    types: Arc<kobold::TypeList>,
+}
+
+#[pyclass(extends = Serializer, subclass)]
+struct CoreObjectSerializer {
+    options: kobold::SerializerOptions,
+    types: Arc<kobold::TypeList>,
+}
+
+#[pymethods]
+impl BinarySerializer {
+    #[new]
+    pub fn new(options: kobold::SerializerOptions, types: &TypeList) -> (Self, Serializer) {
+        (
+            Self {
+                options,
+                types: Arc::clone(&types.inner),
+            },
+            Serializer,
+        )
+    }
+
+    pub fn serialize(&mut self, value: kobold::Value) -> PyResult<Vec<u8>> {
+        let mut scratch = Vec::new();
+        let mut types = (*self.types).clone();
+        kobold::Serializer::<kobold::PropertyClass>::new(self.options.clone(), &mut scratch)
+            .serialize(&value, &mut types)
+            .map_err(|e| KoboldError::new_err(e.to_string()))
+    }
+}
+
+#[pymethods]
+impl CoreObjectSerializer {
+    #[new]
+    pub fn new(options: kobold::SerializerOptions, types: &TypeList) -> (Self, Serializer) {
+        (
+            Self {
+                options,
+                types: Arc::clone(&types.inner),
+            },
+            Serializer,
+        )
+    }
+
+    pub fn serialize(&mut self, value: kobold::Value) -> PyResult<Vec<u8>> {
+        let mut scratch = Vec::new();
+        let mut types = (*self.types).clone();
+        kobold::Serializer::<kobold::CoreObject>::new(self.options.clone(), &mut scratch)
+            .serialize(&value, &mut types)
+            .map_err(|e| KoboldError::new_err(e.to_string()))
+    }
+}
+
+/// Renders a deserialized [`kobold::Value`] as pretty-printed
+/// JSON, e.g. for diffing against a fixture or inspecting game
+/// state by hand.
+#[pyfunction]
+fn to_json(value: kobold::Value) -> PyResult<String> {
+    value
+        .to_json()
+        .map_err(|e| KoboldError::new_err(e.to_string()))
+}
+
+/// Renders a deserialized [`kobold::Value`] as a compact CBOR
+/// document, suitable for storing as a test fixture.
+#[pyfunction]
+fn to_cbor(value: kobold::Value) -> PyResult<Vec<u8>> {
+    value
+        .to_cbor()
+        .map_err(|e| KoboldError::new_err(e.to_string()))
+}
+
 pub fn kobold_op(m: &PyModule) -> PyResult<()> {
     m.add_class::<kobold::DeserializerOptions>()?;
+    m.add_class::<kobold::SerializerOptions>()?;
     m.add_class::<TypeList>()?;
     m.add_class::<Deserializer>()?;
     m.add_class::<BinaryDeserializer>()?;
     m.add_class::<CoreObjectDeserializer>()?;
+    m.add_class::<Serializer>()?;
+    m.add_class::<BinarySerializer>()?;
+    m.add_class::<CoreObjectSerializer>()?;
+    m.add_function(wrap_pyfunction!(to_json, m)?)?;
+    m.add_function(wrap_pyfunction!(to_cbor, m)?)?;
 
     Ok(())
 }